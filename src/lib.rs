@@ -1,5 +1,8 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use clap::{App, Arg, ArgMatches};
-use combostew::config::{Config, ConfigItem, FormatEncodingSettings, JPEGEncodingSettings, PNMEncodingSettings, SelectedLicenses};
+use combostew::config::{AVIFEncodingSettings, Config, ConfigItem, FormatEncodingSettings, JPEGEncodingSettings, PNMEncodingSettings, SelectedLicenses};
 use combostew::io::{export, import};
 use combostew::operations::Operation;
 use combostew::processor::{ProcessMutWithConfig, ProcessWithConfig};
@@ -7,6 +10,15 @@ use combostew::processor::encoding_format::EncodingFormatDecider;
 use combostew::processor::image_operations::ImageOperationsProcessor;
 use combostew::processor::license_display::LicenseDisplayProcessor;
 
+mod style;
+mod verbosity;
+
+use style::ColorChoice;
+use verbosity::LogLevel;
+
+/// Passed to `--input`/`--output`, this tells Stew to use stdin/stdout instead of a file path.
+const STDIO_SENTINEL: &str = "-";
+
 pub fn get_app_skeleton(name: &str) -> App<'static, 'static> {
     App::new(name)
         .author("Martijn Gribnau <garm@ilumeo.com>")
@@ -15,7 +27,7 @@ pub fn get_app_skeleton(name: &str) -> App<'static, 'static> {
             .long("output-format")
             .value_name("FORMAT")
             .help("Force the output image format to use FORMAT, regardless of the (if any) extension of the given output file path. \
-                Output formats (FORMAT values) supported: BMP, GIF, ICO, JPEG, PNG, PBM, PGM, PPM and PAM.")
+                Output formats (FORMAT values) supported: AVIF, BMP, GIF, ICO, JPEG, PNG, PBM, PGM, PPM and PAM.")
             .takes_value(true))
         .arg(Arg::with_name("license")
             .long("license")
@@ -30,6 +42,31 @@ pub fn get_app_skeleton(name: &str) -> App<'static, 'static> {
             .help("Set the jpeg quality to QUALITY. Valid values are natural numbers from 1 up to and including 100. Will only be used when the output format is determined to be jpeg.")
             .value_name("QUALITY")
             .takes_value(true))
+        .arg(Arg::with_name("log_level")
+            .long("log-level")
+            .help("Set how much progress/diagnostic detail Stew prints to stderr while it runs. \
+                Defaults to 'warn'.")
+            .value_name("LEVEL")
+            .possible_values(&["trace", "debug", "info", "warn", "error"])
+            .takes_value(true))
+        .arg(Arg::with_name("color")
+            .long("color")
+            .help("Control whether diagnostic output (warnings and progress messages) is styled with ANSI colors. \
+                'auto' (the default) styles only when stderr is a terminal, 'always' forces styling and 'never' disables it. \
+                License text is not styled.")
+            .value_name("WHEN")
+            .possible_values(&["auto", "always", "never"])
+            .takes_value(true))
+        .arg(Arg::with_name("avif_quality")
+            .long("avif-quality")
+            .help("Set the AVIF quality to QUALITY. Valid values are natural numbers from 1 up to and including 100. Will only be used when the output format is determined to be avif.")
+            .value_name("QUALITY")
+            .takes_value(true))
+        .arg(Arg::with_name("avif_encoding_speed")
+            .long("avif-encoding-speed")
+            .help("Set the AVIF encoding speed to SPEED. Valid values are natural numbers from 1 up to and including 10, where 1 is slowest (best compression) and 10 is fastest. Will only be used when the output format is determined to be avif.")
+            .value_name("SPEED")
+            .takes_value(true))
         .arg(Arg::with_name("pnm_encoding_ascii")
             .long("pnm-encoding-ascii")
             .help("Use ascii based encoding when using a PNM image output format (pbm, pgm or ppm). Doesn't apply to 'pam' (PNM Arbitrary Map)."))
@@ -41,13 +78,22 @@ pub fn get_app_skeleton(name: &str) -> App<'static, 'static> {
             .short("i")
             .value_name("FILE_INPUT")
             .takes_value(true)
-            .help("Input image path. When using this option, input piped from stdin will be ignored."))
+            .multiple(true)
+            .help("Input image path. Accepts multiple values (repeat -i, or pass several paths) and \
+                glob patterns such as '*.png', in which case the same operations are applied to every \
+                matched image. When using this option, input piped from stdin will be ignored. \
+                Use a single '-' to read one image from stdin explicitly; '-' can't be combined with \
+                other inputs."))
         .arg(Arg::with_name("output")
             .long("output")
             .short("o")
             .value_name("FILE_OUTPUT")
             .takes_value(true)
-            .help("Output image path. When using this option, output won't be piped to stdout."))
+            .help("Output image path. When using this option, output won't be piped to stdout. \
+                Use a single '-' to write the encoded image to stdout explicitly (only valid with a \
+                single input). When --input matches more than one image, this must instead be a \
+                directory, or a filename template containing '{name}' and/or '{ext}' placeholders \
+                (e.g. 'out/{name}.{ext}'), one output being derived from each input."))
 }
 
 // Here any option should not panic when invalid.
@@ -87,12 +133,26 @@ pub fn get_default_config(
                 matches.value_of("jpeg_encoding_quality"),
             ))?,
             pnm_settings: PNMEncodingSettings::new(matches.is_present("pnm_encoding_ascii")),
+            // Same 3 possibilities as jpeg_settings above, but for the quality and encoding
+            // speed of the avif encoder, which are only applied when the resolved format is avif.
+            avif_settings: AVIFEncodingSettings::new_result(
+                (
+                    matches.is_present("avif_quality"),
+                    matches.value_of("avif_quality"),
+                ),
+                (
+                    matches.is_present("avif_encoding_speed"),
+                    matches.value_of("avif_encoding_speed"),
+                ),
+            )?,
         },
 
         // TODO: output_file is sic specific
+        // A bare "-" is the stdout sentinel, not a literal file named "-".
         output: matches
             .value_of("output")
             .or_else(|| matches.value_of("output_file"))
+            .filter(|v| *v != STDIO_SENTINEL)
             .map(|v| v.into()),
 
         application_specific: app_config,
@@ -101,33 +161,280 @@ pub fn get_default_config(
     Ok(res)
 }
 
+/// Build a default output path of the form `<unix_timestamp>-<tool_name>.<ext>`, used when the
+/// user didn't provide `--output` (and didn't explicitly ask for stdout via `-` either).
+fn default_output_path(tool_name: &str, ext: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    PathBuf::from(format!("{}-{}.{}", timestamp, tool_name, ext))
+}
+
+/// A value passed to `--input` is a glob pattern, rather than a literal path, if it contains
+/// any of the usual glob metacharacters.
+fn looks_like_glob(value: &str) -> bool {
+    value.contains('*') || value.contains('?') || value.contains('[')
+}
+
+/// Resolves the raw `--input` values into a concrete, ordered list of input paths, expanding
+/// any glob patterns. `None` entries are the stdin sentinel; since there's no concrete path to
+/// read several images from stdin, a bare "-" is only valid on its own, and so is an absent
+/// `--input` (which, as before, falls back to stdin).
+fn resolve_inputs(matches: &ArgMatches) -> Result<Vec<Option<PathBuf>>, String> {
+    let raw: Vec<&str> = matches
+        .values_of("input")
+        .map(Iterator::collect)
+        .or_else(|| matches.value_of("input_file").map(|v| vec![v]))
+        .unwrap_or_default();
+
+    if raw.is_empty() {
+        return Ok(vec![None]);
+    }
+
+    if raw.contains(&STDIO_SENTINEL) {
+        return if raw.len() == 1 {
+            Ok(vec![None])
+        } else {
+            Err("'-' (stdin) can't be combined with other --input values.".to_string())
+        };
+    }
+
+    let mut inputs = Vec::new();
+    for value in raw {
+        if looks_like_glob(value) {
+            for entry in glob::glob(value).map_err(|err| err.to_string())? {
+                inputs.push(Some(entry.map_err(|err| err.to_string())?));
+            }
+        } else {
+            inputs.push(Some(PathBuf::from(value)));
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err("--input pattern(s) matched no files.".to_string());
+    }
+
+    Ok(inputs)
+}
+
+/// A target that doesn't exist yet still "looks like" a directory (rather than a file to be
+/// written) when it's given with a trailing path separator, or has no file extension.
+fn looks_like_directory_target(target: &Path) -> bool {
+    let as_str = target.to_string_lossy();
+
+    as_str.ends_with('/')
+        || as_str.ends_with(std::path::MAIN_SEPARATOR)
+        || target.extension().is_none()
+}
+
+/// Derives one image's output path from the user-specified output target — a single file, a
+/// directory, or a `{name}`/`{ext}` template — plus its resolved encoding extension. `target`
+/// is `None` when `--output` was absent, in which case a timestamped default is used.
+fn resolve_output_path(
+    target: Option<&Path>,
+    input_path: Option<&Path>,
+    tool_name: &str,
+    ext: &str,
+) -> PathBuf {
+    let name = input_path
+        .and_then(Path::file_stem)
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("stdin");
+
+    match target {
+        None => default_output_path(tool_name, ext),
+        Some(target) if target.is_dir() => target.join(format!("{}.{}", name, ext)),
+        Some(target) => {
+            let template = target.to_string_lossy();
+            if template.contains("{name}") || template.contains("{ext}") {
+                PathBuf::from(template.replace("{name}", name).replace("{ext}", ext))
+            } else {
+                target.to_path_buf()
+            }
+        }
+    }
+}
+
 /// The run function runs the sic application, taking the matches found by Clap.
 /// This function is separated from the main() function so that it can be used more easily in test cases.
 /// This function consumes the matches provided.
 pub fn run(
     matches: &ArgMatches,
     operations: &mut [Operation],
-    options: &Config,
+    options: &mut Config,
 ) -> Result<(), String> {
-    if options.output.is_none() {
-        eprintln!(
-            "The default output format is BMP. Use --output-format <FORMAT> to specify \
-             a different output format."
-        );
+    let color = ColorChoice::from_flag(matches.value_of("color"));
+    let log_level = LogLevel::from_flag(matches.value_of("log_level"));
+
+    // A bare "-" for --output is an explicit request for stdout, not an absent option.
+    let output_is_stdout_sentinel = matches.value_of("output") == Some(STDIO_SENTINEL);
+    let output_target = if output_is_stdout_sentinel {
+        None
+    } else {
+        options.output.clone()
+    };
+
+    let inputs = resolve_inputs(matches)?;
+
+    if inputs.len() > 1 {
+        if output_is_stdout_sentinel {
+            return Err(
+                "stdout ('-') can't be used as --output with multiple --input files.".to_string(),
+            );
+        }
+
+        // A template's directory is created per-image below (resolve_output_path substitutes
+        // the template before we know the real directory), and a plain directory target is
+        // created right after this check; this only checks that --output *names* a directory
+        // or a template.
+        let is_batch_target = output_target.as_deref().is_some_and(|target| {
+            let as_str = target.to_string_lossy();
+            as_str.contains("{name}")
+                || as_str.contains("{ext}")
+                || target.is_dir()
+                || looks_like_directory_target(target)
+        });
+
+        if !is_batch_target {
+            return Err(
+                "--output must be a directory, or a template containing '{name}'/'{ext}', when \
+                 --input matches more than one file."
+                    .to_string(),
+            );
+        }
+
+        // Create a plain directory target that doesn't exist yet, so resolve_output_path
+        // writes each image into it rather than every image over one file of that name.
+        if let Some(target) = output_target.as_deref() {
+            let as_str = target.to_string_lossy();
+            if !as_str.contains("{name}") && !as_str.contains("{ext}") && !target.is_dir() {
+                std::fs::create_dir_all(target).map_err(|err| {
+                    format!("failed to create --output directory {}: {}", target.display(), err)
+                })?;
+            }
+        }
     }
 
-    // TODO: This should be reworked, since "input_file" is sic specific.
-    let mut img = import(
-        matches
-            .value_of("input")
-            .or_else(|| matches.value_of("input_file")),
-    )?;
+    let ext = options
+        .forced_output_format
+        .as_deref()
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_else(|| "bmp".to_string());
+
+    verbosity::log(
+        log_level,
+        LogLevel::Debug,
+        color,
+        &format!("Resolved {} input image(s).", inputs.len()),
+    );
+
+    let mut seen_outputs: std::collections::HashSet<PathBuf> = std::collections::HashSet::new();
+
+    for input_path in &inputs {
+        let input_display = input_path
+            .as_deref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "stdin".to_string());
+
+        // Resolve and finalize this image's output path before import/process_mut run, so
+        // they see this image's real target, not the previous iteration's (or, on the first
+        // iteration, the raw, unresolved batch target).
+        options.output = if output_is_stdout_sentinel {
+            None
+        } else {
+            let path = resolve_output_path(
+                output_target.as_deref(),
+                input_path.as_deref(),
+                options.tool_name,
+                &ext,
+            );
 
-    let mut image_operations_processor = ImageOperationsProcessor::new(&mut img, operations);
-    image_operations_processor.process_mut(&options)?;
+            // Create the resolved path's parent directory if needed. This is evaluated after
+            // template substitution, so it works for both a plain directory target and a
+            // '{name}'/'{ext}' template — the latter's raw, unsubstituted form can't be used
+            // to find the real directory (e.g. "out/{name}.{ext}" has no existing parent to
+            // check until {name}/{ext} are filled in).
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() && !parent.is_dir() {
+                    std::fs::create_dir_all(parent).map_err(|err| {
+                        format!(
+                            "failed to create --output directory {}: {}",
+                            parent.display(),
+                            err
+                        )
+                    })?;
+                }
+            }
 
-    let format_decider = EncodingFormatDecider::default();
-    export(&img, &format_decider, &options)
+            if output_target.is_none() {
+                // Warn, not info: this is the one message that matters most when the user
+                // passes no flags at all, and --log-level defaults to warn.
+                verbosity::log(
+                    log_level,
+                    LogLevel::Warn,
+                    color,
+                    &format!("No --output given, writing to {}.", path.display()),
+                );
+            }
+
+            if !seen_outputs.insert(path.clone()) {
+                verbosity::log(
+                    log_level,
+                    LogLevel::Warn,
+                    color,
+                    &format!(
+                        "{} is the output path for more than one input image in this run; \
+                         earlier output(s) will be overwritten.",
+                        path.display()
+                    ),
+                );
+            }
+
+            Some(path)
+        };
+
+        verbosity::log(
+            log_level,
+            LogLevel::Debug,
+            color,
+            &format!("Importing image from {}.", input_display),
+        );
+
+        let mut img = import(input_path.as_deref().and_then(Path::to_str))?;
+
+        let mut image_operations = operations.to_vec();
+        verbosity::log(
+            log_level,
+            LogLevel::Debug,
+            color,
+            &format!("Applying {} image operation(s).", image_operations.len()),
+        );
+
+        let mut image_operations_processor =
+            ImageOperationsProcessor::new(&mut img, &mut image_operations);
+        image_operations_processor.process_mut(options)?;
+
+        verbosity::log(
+            log_level,
+            LogLevel::Info,
+            color,
+            &match (options.forced_output_format.as_deref(), options.output.as_ref()) {
+                (Some(format), _) => format!("Encoding using the forced format {}.", format),
+                (None, Some(_)) => {
+                    "Encoding using the format derived from the output path's extension."
+                        .to_string()
+                }
+                (None, None) => "Encoding to stdout using the default format BMP.".to_string(),
+            },
+        );
+
+        let format_decider = EncodingFormatDecider::default();
+        export(&img, &format_decider, options)?;
+    }
+
+    Ok(())
 }
 
 pub fn run_display_licenses(
@@ -135,6 +442,8 @@ pub fn run_display_licenses(
     tool_name: &'static str,
     app_config: Vec<ConfigItem>,
 ) -> Result<(), String> {
+    // LicenseDisplayProcessor lives in combostew and writes its own, unstyled output;
+    // it doesn't go through style::paint, so --color has no effect on it.
     let options = get_default_config(&matches, tool_name, app_config)?;
 
     let license_display_processor = LicenseDisplayProcessor::default();
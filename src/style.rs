@@ -0,0 +1,71 @@
+//! A small ANSI styling module for terminal diagnostics (warnings, progress messages, ...).
+//! License output is written by combostew and is not styled by this module.
+//!
+//! Styling goes through the single [`paint`] entry point, which is disabled automatically
+//! when the target stream isn't a TTY, or explicitly overridden with `--color`.
+
+/// A named terminal effect, mapped to its ANSI SGR (Select Graphic Rendition) code.
+///
+/// Only the variants actually used by a call site in this crate are listed here; add more
+/// (e.g. Underline, Inverse, Green, Cyan) once something needs them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Bold,
+    Dim,
+    Red,
+    Yellow,
+}
+
+impl Effect {
+    fn sgr_code(self) -> &'static str {
+        match self {
+            Effect::Bold => "1",
+            Effect::Dim => "2",
+            Effect::Red => "31",
+            Effect::Yellow => "33",
+        }
+    }
+}
+
+/// Whether ANSI styling should be emitted at all. Resolved once from `--color` and
+/// consulted by every call to [`paint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn from_flag(value: Option<&str>) -> ColorChoice {
+        match value {
+            Some("always") => ColorChoice::Always,
+            Some("never") => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        }
+    }
+
+    /// Resolves this choice against whether stderr is currently a TTY.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => atty::is(atty::Stream::Stderr),
+        }
+    }
+}
+
+/// Wraps `text` in the SGR codes for `effects`, unless `choice` resolves to disabled.
+pub fn paint(choice: ColorChoice, effects: &[Effect], text: &str) -> String {
+    if !choice.enabled() || effects.is_empty() {
+        return text.to_string();
+    }
+
+    let codes = effects
+        .iter()
+        .map(|effect| effect.sgr_code())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    format!("\x1b[{}m{}\x1b[0m", codes, text)
+}
@@ -0,0 +1,47 @@
+//! A small verbosity ladder for the progress/diagnostic messages Stew prints while it runs,
+//! independent of any logging backend. Ordered from least to most verbose, mirroring the
+//! usual `error < warn < info < debug < trace` convention.
+
+use crate::style::{self, ColorChoice, Effect};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn from_flag(value: Option<&str>) -> LogLevel {
+        match value {
+            Some("trace") => LogLevel::Trace,
+            Some("debug") => LogLevel::Debug,
+            Some("info") => LogLevel::Info,
+            Some("error") => LogLevel::Error,
+            _ => LogLevel::Warn,
+        }
+    }
+
+    /// Whether a message logged at `message_level` should be emitted given this threshold.
+    fn allows(self, message_level: LogLevel) -> bool {
+        message_level <= self
+    }
+}
+
+/// Prints `message` to stderr, styled by `message_level`, if `threshold` allows it.
+pub fn log(threshold: LogLevel, message_level: LogLevel, color: ColorChoice, message: &str) {
+    if !threshold.allows(message_level) {
+        return;
+    }
+
+    let effects: &[Effect] = match message_level {
+        LogLevel::Error => &[Effect::Bold, Effect::Red],
+        LogLevel::Warn => &[Effect::Yellow],
+        LogLevel::Info => &[],
+        LogLevel::Debug | LogLevel::Trace => &[Effect::Dim],
+    };
+
+    eprintln!("{}", style::paint(color, effects, message));
+}